@@ -8,7 +8,7 @@ use rspack_core::{
   DependencyId, LibIdentOptions, Module, ModuleIdentifier, ModuleType, RuntimeGlobals, RuntimeSpec,
   SourceType,
 };
-use rspack_error::{impl_empty_diagnosable_trait, Result};
+use rspack_error::{Diagnosable, Diagnostic, Result};
 use rspack_hash::RspackHash;
 use rspack_identifier::{Identifiable, Identifier};
 
@@ -18,6 +18,85 @@ use super::{
 };
 use crate::{utils::json_stringify, ConsumeOptions};
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConsumeVersionFilter {
+  pub version: Option<String>,
+  pub fallback: bool,
+}
+
+fn version_filter_arg(filter: Option<&ConsumeVersionFilter>) -> String {
+  let Some(filter) = filter else {
+    return "undefined".to_string();
+  };
+  let range = filter
+    .version
+    .as_ref()
+    .map(|v| format!("loaders.parseRange({})", json_stringify(v)))
+    .unwrap_or_else(|| "undefined".to_string());
+  format!("[{range}, {}]", filter.fallback)
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConsumeFallbackBehavior {
+  #[default]
+  Warn,
+  Throw,
+  Silent,
+}
+
+fn format_version_filter(label: &str, filter: &ConsumeVersionFilter) -> String {
+  format!(
+    " ({label}: {}{})",
+    filter.version.as_deref().unwrap_or("*"),
+    filter.fallback.then_some(", fallback").unwrap_or_default()
+  )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_identifier(
+  share_scope: &str,
+  share_key: &str,
+  required_version: Option<&str>,
+  layer: Option<&str>,
+  strict_version: bool,
+  singleton: bool,
+  import_resolved: Option<&str>,
+  eager: bool,
+  include: Option<&ConsumeVersionFilter>,
+  exclude: Option<&ConsumeVersionFilter>,
+  fallback_behavior: ConsumeFallbackBehavior,
+  has_import: bool,
+) -> String {
+  format!(
+    "consume shared module ({share_scope}) {share_key}@{}{}{}{}{}{}{}{}{}",
+    required_version.unwrap_or("*"),
+    layer
+      .map(|layer| format!(" (layer: {layer})"))
+      .unwrap_or_default(),
+    strict_version.then_some(" (strict)").unwrap_or_default(),
+    singleton.then_some(" (strict)").unwrap_or_default(),
+    import_resolved
+      .map(|f| format!(" (fallback: {f})"))
+      .unwrap_or_default(),
+    eager.then_some(" (eager)").unwrap_or_default(),
+    include
+      .map(|f| format_version_filter("include", f))
+      .unwrap_or_default(),
+    exclude
+      .map(|f| format_version_filter("exclude", f))
+      .unwrap_or_default(),
+    if has_import {
+      ""
+    } else {
+      match fallback_behavior {
+        ConsumeFallbackBehavior::Warn => "",
+        ConsumeFallbackBehavior::Throw => " (fallback behavior: throw)",
+        ConsumeFallbackBehavior::Silent => " (fallback behavior: silent)",
+      }
+    },
+  )
+}
+
 #[derive(Debug)]
 pub struct ConsumeSharedModule {
   blocks: Vec<AsyncDependenciesBlockId>,
@@ -29,37 +108,37 @@ pub struct ConsumeSharedModule {
   options: ConsumeOptions,
   build_info: Option<BuildInfo>,
   build_meta: Option<BuildMeta>,
+  diagnostics: Vec<Diagnostic>,
 }
 
 impl ConsumeSharedModule {
   pub fn new(context: Context, options: ConsumeOptions) -> Self {
-    let identifier = format!(
-      "consume shared module ({}) {}@{}{}{}{}{}",
+    let required_version = options.required_version.as_ref().map(|v| v.to_string());
+    let identifier = build_identifier(
       &options.share_scope,
       &options.share_key,
-      options
-        .required_version
-        .as_ref()
-        .map(|v| v.to_string())
-        .unwrap_or_else(|| "*".to_string()),
-      options
-        .strict_version
-        .then_some(" (strict)")
-        .unwrap_or_default(),
-      options.singleton.then_some(" (strict)").unwrap_or_default(),
-      options
-        .import_resolved
-        .as_ref()
-        .map(|f| format!(" (fallback: {f})"))
-        .unwrap_or_default(),
-      options.eager.then_some(" (eager)").unwrap_or_default(),
+      required_version.as_deref(),
+      options.layer.as_deref(),
+      options.strict_version,
+      options.singleton,
+      options.import_resolved.as_deref(),
+      options.eager,
+      options.include.as_ref(),
+      options.exclude.as_ref(),
+      options.fallback_behavior,
+      options.import.is_some(),
     );
     Self {
       blocks: Vec::new(),
       dependencies: Vec::new(),
       identifier: ModuleIdentifier::from(identifier.clone()),
       lib_ident: format!(
-        "webpack/sharing/consume/{}/{}{}",
+        "webpack/sharing/consume/{}{}/{}{}",
+        options
+          .layer
+          .as_ref()
+          .map(|layer| format!("{layer}/"))
+          .unwrap_or_default(),
         &options.share_scope,
         &options.share_key,
         options
@@ -73,8 +152,93 @@ impl ConsumeSharedModule {
       options,
       build_info: None,
       build_meta: None,
+      diagnostics: Vec::new(),
+    }
+  }
+
+  fn qualified_share_scope(&self) -> String {
+    qualify_share_scope(&self.options.share_scope, self.options.layer.as_deref())
+  }
+
+  fn refresh_missing_version_diagnostic(&mut self) {
+    self.diagnostics.clear();
+    if let Some(diagnostic) = missing_version_diagnostic(
+      &self.options.share_scope,
+      &self.options.share_key,
+      self.options.required_version.is_none(),
+      self.options.import.is_none(),
+    ) {
+      self.diagnostics.push(diagnostic);
+    }
+  }
+}
+
+fn missing_version_diagnostic(
+  share_scope: &str,
+  share_key: &str,
+  missing_required_version: bool,
+  missing_import: bool,
+) -> Option<Diagnostic> {
+  if !missing_required_version || !missing_import {
+    return None;
+  }
+  Some(Diagnostic::warn(
+    "ConsumeSharedModule".to_string(),
+    format!(
+      "No required version and no fallback import specified for shared module {share_scope}@{share_key}. \
+       This will consume whatever singleton happens to be present in the share scope, \
+       which can silently resolve to an unexpected version.",
+    ),
+  ))
+}
+
+fn qualify_share_scope(share_scope: &str, layer: Option<&str>) -> String {
+  match layer {
+    Some(layer) => format!("{share_scope}/{layer}"),
+    None => share_scope.to_string(),
+  }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn select_runtime_call(
+  required_version: Option<&str>,
+  strict_version: bool,
+  singleton: bool,
+  include: Option<&ConsumeVersionFilter>,
+  exclude: Option<&ConsumeVersionFilter>,
+  fallback_behavior: ConsumeFallbackBehavior,
+  has_import: bool,
+) -> (String, Vec<String>) {
+  let mut function = String::from("loaders.load");
+  let mut extra_args = Vec::new();
+  if let Some(version) = required_version {
+    if strict_version {
+      function += "Strict";
+    }
+    if singleton {
+      function += "Singleton";
     }
+    extra_args.push(format!(
+      "loaders.parseRange({})",
+      json_stringify(&version.to_string())
+    ));
+    function += "VersionCheck";
+  } else if singleton {
+    function += "Singleton";
+  }
+  if include.is_some() || exclude.is_some() {
+    function += "Filtered";
+    extra_args.push(version_filter_arg(include));
+    extra_args.push(version_filter_arg(exclude));
+  }
+  if !has_import {
+    function += match fallback_behavior {
+      ConsumeFallbackBehavior::Warn => "",
+      ConsumeFallbackBehavior::Throw => "OrThrow",
+      ConsumeFallbackBehavior::Silent => "OrSilent",
+    };
   }
+  (function, extra_args)
 }
 
 impl Identifiable for ConsumeSharedModule {
@@ -134,6 +298,8 @@ impl Module for ConsumeSharedModule {
   }
 
   async fn build(&mut self, build_context: BuildContext<'_>) -> Result<BuildResult> {
+    self.refresh_missing_version_diagnostic();
+
     let mut hasher = RspackHash::from(&build_context.compiler_options.output);
     self.update_hash(&mut hasher);
     let hash = hasher.digest(&build_context.compiler_options.output.hash_digest);
@@ -173,24 +339,25 @@ impl Module for ConsumeSharedModule {
     code_generation_result
       .runtime_requirements
       .insert(RuntimeGlobals::SHARE_SCOPE_MAP);
-    let mut function = String::from("loaders.load");
     let mut args = vec![
-      json_stringify(&self.options.share_scope),
+      json_stringify(&self.qualified_share_scope()),
       json_stringify(&self.options.share_key),
     ];
-    if let Some(version) = &self.options.required_version {
-      if self.options.strict_version {
-        function += "Strict";
-      }
-      if self.options.singleton {
-        function += "Singleton";
-      }
-      let version = json_stringify(&version.to_string());
-      args.push(format!("loaders.parseRange({})", version));
-      function += "VersionCheck";
-    } else if self.options.singleton {
-      function += "Singleton";
-    }
+    let required_version = self
+      .options
+      .required_version
+      .as_ref()
+      .map(|v| v.to_string());
+    let (function, extra_args) = select_runtime_call(
+      required_version.as_deref(),
+      self.options.strict_version,
+      self.options.singleton,
+      self.options.include.as_ref(),
+      self.options.exclude.as_ref(),
+      self.options.fallback_behavior,
+      self.options.import.is_some(),
+    );
+    args.extend(extra_args);
     let factory = self.options.import.as_ref().map(|fallback| {
       if self.options.eager {
         sync_module_factory(
@@ -211,20 +378,28 @@ impl Module for ConsumeSharedModule {
     code_generation_result
       .data
       .insert(CodeGenerationDataConsumeShared {
-        share_scope: self.options.share_scope.clone(),
+        share_scope: self.qualified_share_scope(),
         share_key: self.options.share_key.clone(),
+        layer: self.options.layer.clone(),
         import: self.options.import.clone(),
         required_version: self.options.required_version.clone(),
         strict_version: self.options.strict_version,
         singleton: self.options.singleton,
         eager: self.options.eager,
+        include: self.options.include.clone(),
+        exclude: self.options.exclude.clone(),
+        fallback_behavior: self.options.fallback_behavior,
         fallback: factory,
       });
     Ok(code_generation_result)
   }
 }
 
-impl_empty_diagnosable_trait!(ConsumeSharedModule);
+impl Diagnosable for ConsumeSharedModule {
+  fn diagnostics(&self) -> Cow<[Diagnostic]> {
+    Cow::Borrowed(&self.diagnostics)
+  }
+}
 
 impl Hash for ConsumeSharedModule {
   fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
@@ -240,3 +415,242 @@ impl PartialEq for ConsumeSharedModule {
 }
 
 impl Eq for ConsumeSharedModule {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn version_filter_arg_absent() {
+    assert_eq!(version_filter_arg(None), "undefined");
+  }
+
+  #[test]
+  fn version_filter_arg_without_version() {
+    let filter = ConsumeVersionFilter {
+      version: None,
+      fallback: false,
+    };
+    assert_eq!(version_filter_arg(Some(&filter)), "[undefined, false]");
+  }
+
+  #[test]
+  fn version_filter_arg_with_version_and_fallback() {
+    let filter = ConsumeVersionFilter {
+      version: Some("^1.0.0".to_string()),
+      fallback: true,
+    };
+    assert_eq!(
+      version_filter_arg(Some(&filter)),
+      "[loaders.parseRange(\"^1.0.0\"), true]"
+    );
+  }
+
+  #[test]
+  fn format_version_filter_wildcard() {
+    let filter = ConsumeVersionFilter {
+      version: None,
+      fallback: false,
+    };
+    assert_eq!(format_version_filter("include", &filter), " (include: *)");
+  }
+
+  #[test]
+  fn format_version_filter_with_version_and_fallback() {
+    let filter = ConsumeVersionFilter {
+      version: Some("1.4.2".to_string()),
+      fallback: true,
+    };
+    assert_eq!(
+      format_version_filter("exclude", &filter),
+      " (exclude: 1.4.2, fallback)"
+    );
+  }
+
+  #[test]
+  fn select_runtime_call_with_include_and_exclude_is_filtered_with_both_args() {
+    let include = ConsumeVersionFilter {
+      version: Some("^1.0".to_string()),
+      fallback: false,
+    };
+    let exclude = ConsumeVersionFilter {
+      version: Some("1.4.2".to_string()),
+      fallback: false,
+    };
+    let (function, args) = select_runtime_call(
+      None,
+      false,
+      false,
+      Some(&include),
+      Some(&exclude),
+      ConsumeFallbackBehavior::Warn,
+      true,
+    );
+    assert_eq!(function, "loaders.loadFiltered");
+    assert_eq!(
+      args,
+      vec![
+        version_filter_arg(Some(&include)),
+        version_filter_arg(Some(&exclude))
+      ]
+    );
+  }
+
+  #[test]
+  fn build_identifier_orders_include_before_exclude() {
+    let include = ConsumeVersionFilter {
+      version: Some("^1.0".to_string()),
+      fallback: false,
+    };
+    let exclude = ConsumeVersionFilter {
+      version: Some("1.4.2".to_string()),
+      fallback: false,
+    };
+    let identifier = build_identifier(
+      "default",
+      "react",
+      None,
+      None,
+      false,
+      false,
+      None,
+      false,
+      Some(&include),
+      Some(&exclude),
+      ConsumeFallbackBehavior::Warn,
+      true,
+    );
+    assert_eq!(
+      identifier,
+      "consume shared module (default) react@* (include: ^1.0) (exclude: 1.4.2)"
+    );
+  }
+
+  #[test]
+  fn qualify_share_scope_without_layer() {
+    assert_eq!(qualify_share_scope("default", None), "default");
+  }
+
+  #[test]
+  fn qualify_share_scope_with_layer() {
+    assert_eq!(
+      qualify_share_scope("default", Some("server")),
+      "default/server"
+    );
+  }
+
+  #[test]
+  fn build_identifier_places_layer_before_strict_and_singleton() {
+    let identifier = build_identifier(
+      "default",
+      "react",
+      Some("^18.0.0"),
+      Some("server"),
+      true,
+      true,
+      None,
+      false,
+      None,
+      None,
+      ConsumeFallbackBehavior::Warn,
+      true,
+    );
+    assert_eq!(
+      identifier,
+      "consume shared module (default) react@^18.0.0 (layer: server) (strict) (strict)"
+    );
+  }
+
+  #[test]
+  fn missing_version_diagnostic_present_without_version_or_import() {
+    let diagnostic = missing_version_diagnostic("default", "react", true, true);
+    assert!(diagnostic.is_some());
+  }
+
+  #[test]
+  fn missing_version_diagnostic_absent_with_required_version() {
+    assert!(missing_version_diagnostic("default", "react", false, true).is_none());
+  }
+
+  #[test]
+  fn missing_version_diagnostic_absent_with_import() {
+    assert!(missing_version_diagnostic("default", "react", true, false).is_none());
+  }
+
+  #[test]
+  fn missing_version_diagnostic_does_not_accumulate_across_rebuilds() {
+    // Repeats the clear-then-push steps of `refresh_missing_version_diagnostic`
+    // on a local vector; the module itself is not built here.
+    let mut diagnostics = Vec::new();
+    for _ in 0..2 {
+      diagnostics.clear();
+      if let Some(diagnostic) = missing_version_diagnostic("default", "react", true, true) {
+        diagnostics.push(diagnostic);
+      }
+    }
+    assert_eq!(diagnostics.len(), 1);
+  }
+
+  #[test]
+  fn select_runtime_call_suffixes_fallback_behavior_only_without_import() {
+    let (function, _) = select_runtime_call(
+      None,
+      false,
+      false,
+      None,
+      None,
+      ConsumeFallbackBehavior::Throw,
+      false,
+    );
+    assert_eq!(function, "loaders.loadOrThrow");
+
+    let (function, _) = select_runtime_call(
+      None,
+      false,
+      false,
+      None,
+      None,
+      ConsumeFallbackBehavior::Throw,
+      true,
+    );
+    assert_eq!(function, "loaders.load");
+  }
+
+  #[test]
+  fn build_identifier_encodes_fallback_behavior_only_without_import() {
+    let with_import = build_identifier(
+      "default",
+      "react",
+      None,
+      None,
+      false,
+      false,
+      None,
+      false,
+      None,
+      None,
+      ConsumeFallbackBehavior::Throw,
+      true,
+    );
+    assert_eq!(with_import, "consume shared module (default) react@*");
+
+    let without_import = build_identifier(
+      "default",
+      "react",
+      None,
+      None,
+      false,
+      false,
+      None,
+      false,
+      None,
+      None,
+      ConsumeFallbackBehavior::Throw,
+      false,
+    );
+    assert_eq!(
+      without_import,
+      "consume shared module (default) react@* (fallback behavior: throw)"
+    );
+  }
+}